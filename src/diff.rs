@@ -0,0 +1,297 @@
+//!
+//! Unified diff generation via the Myers O(ND) shortest-edit-script
+//! algorithm.
+//!
+
+use std::fmt;
+
+use crate::parser::{Context, ContextHeader, Patch, PatchLine};
+
+enum EditOp {
+    Keep { a_idx: usize },
+    Delete { a_idx: usize },
+    Insert { b_idx: usize },
+}
+
+impl Patch {
+    /// Computes a unified diff between `input` and `output` using the
+    /// Myers shortest-edit-script algorithm, attaching `context_lines` of
+    /// surrounding unchanged lines to each hunk.
+    pub fn diff(
+        input: &[String],
+        output: &[String],
+        input_path: impl Into<String>,
+        output_path: impl Into<String>,
+        context_lines: usize,
+    ) -> Patch {
+        let ops = shortest_edit_script(input, output);
+        let contexts = group_into_hunks(&ops, input, output, context_lines);
+
+        Patch {
+            input: input_path.into(),
+            output: output_path.into(),
+            contexts,
+            metadata: None,
+        }
+    }
+}
+
+/// Renders `patch` as unified-diff text, the inverse of
+/// [`PatchProcessor::convert`](crate::parser::PatchProcessor::convert) —
+/// `PatchProcessor::convert(&patch.to_string())` round-trips a patch built
+/// by [`Patch::diff`].
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "--- {}", self.input)?;
+        writeln!(f, "+++ {}", self.output)?;
+
+        for context in &self.contexts {
+            // An empty side is written as the 0-indexed position itself, the
+            // form the parser reads back without decrementing.
+            let start = |l: usize, s: usize| if s > 0 { l + 1 } else { l };
+            writeln!(
+                f,
+                "@@ -{},{} +{},{} @@",
+                start(context.header.file1_l, context.header.file1_s),
+                context.header.file1_s,
+                start(context.header.file2_l, context.header.file2_s),
+                context.header.file2_s
+            )?;
+
+            for line in &context.data {
+                match line {
+                    PatchLine::Context(data, _) => writeln!(f, " {}", data)?,
+                    PatchLine::Delete(data, _) => writeln!(f, "-{}", data)?,
+                    PatchLine::Insert(data, _) => writeln!(f, "+{}", data)?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs the Myers algorithm, growing the furthest-reaching `x` for every
+/// diagonal `k` at each edit distance `d`, then backtracks the recorded
+/// history into a sequence of keep/insert/delete operations.
+fn shortest_edit_script(a: &[String], b: &[String]) -> Vec<EditOp> {
+    if a.is_empty() && b.is_empty() {
+        return Vec::new();
+    }
+
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = n + m;
+    let offset = max as usize;
+    let idx = |k: isize| (k + offset as isize) as usize;
+
+    let mut v = vec![0isize; 2 * max as usize + 1];
+    let mut trace = Vec::new();
+
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[idx(k - 1)] < v[idx(k + 1)]) {
+                v[idx(k + 1)]
+            } else {
+                v[idx(k - 1)] + 1
+            };
+            let mut y = x - k;
+
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+
+            v[idx(k)] = x;
+
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    backtrack(a, b, &trace)
+}
+
+fn backtrack(a: &[String], b: &[String], trace: &[Vec<isize>]) -> Vec<EditOp> {
+    let max = (a.len() + b.len()) as isize;
+    let offset = max as usize;
+    let idx = |k: isize| (k + offset as isize) as usize;
+
+    let mut x = a.len() as isize;
+    let mut y = b.len() as isize;
+    let mut ops = Vec::new();
+
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+
+        let prev_k = if k == -d || (k != d && v[idx(k - 1)] < v[idx(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+
+        let prev_x = v[idx(prev_k)];
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            ops.push(EditOp::Keep { a_idx: x as usize });
+        }
+
+        if d > 0 {
+            if x == prev_x {
+                y -= 1;
+                ops.push(EditOp::Insert { b_idx: y as usize });
+            } else {
+                x -= 1;
+                ops.push(EditOp::Delete { a_idx: x as usize });
+            }
+        }
+
+        x = prev_x;
+        y = prev_y;
+    }
+
+    ops.reverse();
+    ops
+}
+
+/// Maximal runs of consecutive non-`Keep` operations, as half-open ranges
+/// into `ops`.
+fn change_runs(ops: &[EditOp]) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut i = 0;
+    while i < ops.len() {
+        if matches!(ops[i], EditOp::Keep { .. }) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < ops.len() && !matches!(ops[i], EditOp::Keep { .. }) {
+            i += 1;
+        }
+        runs.push((start, i));
+    }
+    runs
+}
+
+fn group_into_hunks(
+    ops: &[EditOp],
+    a: &[String],
+    b: &[String],
+    context_lines: usize,
+) -> Vec<Context> {
+    let runs = change_runs(ops);
+    if runs.is_empty() {
+        return Vec::new();
+    }
+
+    // Merge change runs separated by no more than `2 * context_lines`
+    // unchanged lines into a single hunk.
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in runs {
+        if let Some(last) = merged.last_mut() {
+            if start - last.1 <= 2 * context_lines {
+                last.1 = end;
+                continue;
+            }
+        }
+        merged.push((start, end));
+    }
+
+    let mut a_pos = vec![0usize; ops.len() + 1];
+    let mut b_pos = vec![0usize; ops.len() + 1];
+    for (i, op) in ops.iter().enumerate() {
+        a_pos[i + 1] = a_pos[i] + usize::from(!matches!(op, EditOp::Insert { .. }));
+        b_pos[i + 1] = b_pos[i] + usize::from(!matches!(op, EditOp::Delete { .. }));
+    }
+
+    merged
+        .into_iter()
+        .map(|(start, end)| {
+            let hunk_start = start.saturating_sub(context_lines);
+            let hunk_end = (end + context_lines).min(ops.len());
+            build_context(&ops[hunk_start..hunk_end], a, b, a_pos[hunk_start], b_pos[hunk_start])
+        })
+        .collect()
+}
+
+fn build_context(
+    ops: &[EditOp],
+    a: &[String],
+    b: &[String],
+    file1_l: usize,
+    file2_l: usize,
+) -> Context {
+    // Diff-generated patches have no corresponding patch source text, so
+    // their lines carry an empty span.
+    let no_span = (0, 0);
+
+    let mut data = Vec::with_capacity(ops.len());
+    for op in ops {
+        match op {
+            EditOp::Keep { a_idx, .. } => {
+                data.push(PatchLine::Context(a[*a_idx].clone(), no_span))
+            }
+            EditOp::Delete { a_idx } => data.push(PatchLine::Delete(a[*a_idx].clone(), no_span)),
+            EditOp::Insert { b_idx } => data.push(PatchLine::Insert(b[*b_idx].clone(), no_span)),
+        }
+    }
+
+    let file1_s = ops
+        .iter()
+        .filter(|op| !matches!(op, EditOp::Insert { .. }))
+        .count();
+    let file2_s = ops
+        .iter()
+        .filter(|op| !matches!(op, EditOp::Delete { .. }))
+        .count();
+
+    Context {
+        header: ContextHeader {
+            file1_l,
+            file1_s,
+            file2_l,
+            file2_s,
+        },
+        header_span: (0, 0),
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::PatchProcessor;
+
+    use super::*;
+
+    #[test]
+    fn diff_round_trips_through_display_and_convert() {
+        let input: Vec<String> = ["one", "two", "three", "four"]
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        let output: Vec<String> = ["one", "two point five", "three", "four", "five"]
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+
+        let patch = Patch::diff(&input, &output, "a/f.txt", "b/f.txt", 1);
+
+        let rendered = patch.to_string();
+        let reparsed = PatchProcessor::convert(&rendered).expect("rendered diff should reparse");
+
+        assert_eq!(reparsed.input, "a/f.txt");
+        assert_eq!(reparsed.output, "b/f.txt");
+
+        let applied = PatchProcessor::converted(input, &rendered)
+            .unwrap()
+            .process()
+            .expect("rendered diff should apply cleanly");
+        assert_eq!(applied, output);
+    }
+}
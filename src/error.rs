@@ -0,0 +1,115 @@
+//!
+//! Error types produced while parsing and applying patches.
+//!
+
+use std::fmt;
+use std::num::ParseIntError;
+
+use crate::parser::{Rule, Span};
+
+/// Where a patch failed to apply: the span of the offending line in the
+/// patch source, and the line number in the target text it was compared
+/// (or expected to be found) against.
+#[derive(Debug, Clone, Copy)]
+pub struct MismatchLocation {
+    pub patch_span: Span,
+    pub text_line: usize,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    NotFound(&'static str),
+    MalformedPatch(&'static str),
+    PatchInputMismatch(MismatchLocation),
+    AbruptInput(MismatchLocation),
+    Parse(Box<pest::error::Error<Rule>>),
+    ParseInt(ParseIntError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(what) => write!(f, "could not find {}", what),
+            Error::MalformedPatch(why) => write!(f, "malformed patch: {}", why),
+            Error::PatchInputMismatch(loc) => {
+                write!(f, "patch does not apply at line {}", loc.text_line + 1)
+            }
+            Error::AbruptInput(loc) => {
+                write!(f, "input ended unexpectedly at line {}", loc.text_line + 1)
+            }
+            Error::Parse(err) => write!(f, "{}", err),
+            Error::ParseInt(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<pest::error::Error<Rule>> for Error {
+    fn from(err: pest::error::Error<Rule>) -> Self {
+        Error::Parse(Box::new(err))
+    }
+}
+
+impl From<ParseIntError> for Error {
+    fn from(err: ParseIntError) -> Self {
+        Error::ParseInt(err)
+    }
+}
+
+#[cfg(feature = "diagnostics")]
+impl Error {
+    /// Renders a colored, span-annotated report pointing at the patch line
+    /// that failed to apply alongside the mismatching (or missing) line in
+    /// `text`, in the style of `ariadne::Report`.
+    pub fn report(&self, patch_src: &str, text: &[String]) -> String {
+        use ariadne::{ColorGenerator, Label, Report, ReportKind, Source};
+
+        let (message, loc, note) = match self {
+            Error::PatchInputMismatch(loc) => (
+                "hunk does not match the input",
+                loc,
+                "the input line does not match what this patch line expects",
+            ),
+            Error::AbruptInput(loc) => (
+                "input ended before the patch expected",
+                loc,
+                "the patch expects a line here, but the input has none left",
+            ),
+            _ => return self.to_string(),
+        };
+
+        let mut colors = ColorGenerator::new();
+        let patch_color = colors.next();
+
+        let mut builder = Report::build(ReportKind::Error, (), loc.patch_span.0)
+            .with_message(message)
+            .with_label(
+                Label::new(loc.patch_span.0..loc.patch_span.1)
+                    .with_message("in this patch line")
+                    .with_color(patch_color),
+            )
+            .with_note(note);
+
+        if let Some(actual) = text.get(loc.text_line) {
+            builder = builder.with_note(format!(
+                "input line {} reads: {:?}",
+                loc.text_line + 1,
+                actual
+            ));
+        } else {
+            builder = builder.with_note(format!(
+                "input has no line {}",
+                loc.text_line + 1
+            ));
+        }
+
+        let mut rendered = Vec::new();
+        builder
+            .finish()
+            .write(Source::from(patch_src), &mut rendered)
+            .expect("ariadne report should render to an in-memory buffer");
+
+        String::from_utf8_lossy(&rendered).into_owned()
+    }
+}
@@ -0,0 +1,13 @@
+//!
+//! patch-rs: a small library for parsing and applying unified diffs.
+//!
+
+pub mod diff;
+pub mod error;
+pub mod parser;
+
+pub use error::{Error, MismatchLocation};
+pub use parser::{
+    ApplyOptions, ApplyReport, Context, ContextHeader, Direction, FileMetadata, HunkReport, Patch,
+    PatchLine, PatchProcessor, PatchResult, PatchSet, Span,
+};
@@ -2,9 +2,12 @@
 //! The parser implementation.
 //!
 
+use std::collections::HashMap;
+
 use pest::{iterators::Pair, Parser};
+use pest_derive::Parser;
 
-use crate::error::Error;
+use crate::error::{Error, MismatchLocation};
 
 #[derive(Parser)]
 #[grammar = "../peg/patch.peg"]
@@ -18,12 +21,44 @@ pub struct Patch {
     pub input: String,
     pub output: String,
     pub contexts: Vec<Context>,
+    /// Git extended header metadata (renames, mode changes, binary), if
+    /// the patch carried a `diff --git` header or a bare `Binary files ...
+    /// differ` marker.
+    pub metadata: Option<FileMetadata>,
+}
+
+/// Git extended header metadata for a file patch, as emitted by `git diff`
+/// before the usual `---`/`+++`/`@@` body: renames, copies, mode changes,
+/// and binary diffs (which replace the body with a `Binary files ...
+/// differ` marker, or nothing at all for a pure rename).
+#[derive(Debug, Default, Clone)]
+pub struct FileMetadata {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub old_mode: Option<String>,
+    pub new_mode: Option<String>,
+    pub is_new_file: bool,
+    pub is_deleted_file: bool,
+    pub is_rename: bool,
+    pub is_copy: bool,
+    pub similarity_index: Option<u8>,
+    pub binary: bool,
+}
+
+/// A parsed multi-file patch, as produced by `diff -ru` or `git diff`.
+pub struct PatchSet {
+    pub patches: Vec<Patch>,
 }
 
 pub type PatchResult<T> = Result<T, Error>;
 
+/// A byte range into the original patch source, used to point diagnostics
+/// at the line that produced a [`PatchLine`].
+pub type Span = (usize, usize);
+
 pub struct Context {
     pub header: ContextHeader,
+    pub header_span: Span,
     pub data: Vec<PatchLine>,
 }
 
@@ -36,9 +71,45 @@ pub struct ContextHeader {
 }
 
 pub enum PatchLine {
-    Context(String),
-    Insert(String),
-    Delete(String),
+    Context(String, Span),
+    Insert(String, Span),
+    Delete(String, Span),
+}
+
+/// Which way a patch is applied: forward turns `input` into `output`,
+/// reverse turns `output` back into `input`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+}
+
+/// Options controlling how forgiving [`PatchProcessor::process_with_options`]
+/// is about hunks that no longer match the input exactly, mirroring GNU
+/// `patch`'s `--fuzz` and offset search.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ApplyOptions {
+    /// How many leading/trailing context lines of a hunk may mismatch and
+    /// still be accepted. GNU `patch` allows 0-3.
+    pub fuzz: usize,
+    /// How far from a hunk's recorded line number to search for a position
+    /// where it does match.
+    pub max_offset: usize,
+    /// Compare context/delete lines after collapsing runs of whitespace and
+    /// trimming trailing whitespace, instead of requiring an exact match.
+    pub ignore_whitespace: bool,
+}
+
+/// How a single hunk was applied: how far its actual position differed
+/// from the line number recorded in the patch.
+pub struct HunkReport {
+    pub offset: isize,
+}
+
+/// The result of [`PatchProcessor::process_with_options`].
+pub struct ApplyReport {
+    pub text: Vec<String>,
+    pub hunks: Vec<HunkReport>,
 }
 
 impl PatchProcessor {
@@ -50,135 +121,512 @@ impl PatchProcessor {
     }
 
     pub fn process(&self) -> PatchResult<Vec<String>> {
-        let mut file2_text = Vec::new();
-        let mut file1_ptr: usize = 0;
-
-        for context in &self.patch.contexts {
-            for i in file1_ptr..context.header.file1_l {
-                file2_text.push(
-                    self.text
-                        .get(i)
-                        .ok_or_else(|| Error::AbruptInput(i))?
+        Self::apply(&self.text, &self.patch, Direction::Forward)
+    }
+
+    /// Unapplies the patch, turning `output` text back into `input` text.
+    pub fn process_reverse(&self) -> PatchResult<Vec<String>> {
+        Self::apply(&self.text, &self.patch, Direction::Reverse)
+    }
+
+    /// Applies the patch like [`Self::process`], but tolerates hunks that
+    /// have drifted from their recorded position (searching outward up to
+    /// `options.max_offset`) or whose edge context lines no longer match
+    /// exactly (tolerating up to `options.fuzz` of them), the way GNU
+    /// `patch` does.
+    pub fn process_with_options(&self, options: ApplyOptions) -> PatchResult<ApplyReport> {
+        Self::apply_fuzzy(&self.text, &self.patch, options)
+    }
+
+    pub fn convert(patch: &str) -> PatchResult<Patch> {
+        Self::convert_patches(patch)?
+            .into_iter()
+            .next()
+            .ok_or(Error::NotFound("patch"))
+    }
+
+    fn apply(text: &[String], patch: &Patch, direction: Direction) -> PatchResult<Vec<String>> {
+        if Self::is_binary(patch) {
+            return Ok(text.to_vec());
+        }
+
+        let mut out_text = Vec::new();
+        let mut ptr: usize = 0;
+
+        for context in &patch.contexts {
+            let target = match direction {
+                Direction::Forward => context.header.file1_l,
+                Direction::Reverse => context.header.file2_l,
+            };
+            for i in ptr..target {
+                out_text.push(
+                    text.get(i)
+                        .ok_or_else(|| Error::AbruptInput(Self::abrupt_at(context, i)))?
                         .to_owned(),
                 );
             }
-            file1_ptr = context.header.file1_l;
+            ptr = target;
             for line in &context.data {
-                match line {
-                    PatchLine::Context(ref data) => {
-                        if self
-                            .text
-                            .get(file1_ptr)
-                            .ok_or_else(|| Error::AbruptInput(file1_ptr))?
-                            != data
-                        {
-                            return Err(Error::PatchInputMismatch(file1_ptr));
+                match (direction, line) {
+                    (Direction::Forward, PatchLine::Context(ref data, span))
+                    | (Direction::Reverse, PatchLine::Context(ref data, span)) => {
+                        let actual = text
+                            .get(ptr)
+                            .ok_or_else(|| Error::AbruptInput(Self::abrupt_at(context, ptr)))?;
+                        if actual != data {
+                            return Err(Error::PatchInputMismatch(MismatchLocation {
+                                patch_span: *span,
+                                text_line: ptr,
+                            }));
                         }
-                        file2_text.push(data.to_owned());
-                        file1_ptr += 1;
+                        out_text.push(data.to_owned());
+                        ptr += 1;
                     }
-                    PatchLine::Delete(ref data) => {
-                        if self
-                            .text
-                            .get(file1_ptr)
-                            .ok_or_else(|| Error::AbruptInput(file1_ptr))?
-                            != data
-                        {
-                            return Err(Error::PatchInputMismatch(file1_ptr));
+                    (Direction::Forward, PatchLine::Delete(ref data, span))
+                    | (Direction::Reverse, PatchLine::Insert(ref data, span)) => {
+                        let actual = text
+                            .get(ptr)
+                            .ok_or_else(|| Error::AbruptInput(Self::abrupt_at(context, ptr)))?;
+                        if actual != data {
+                            return Err(Error::PatchInputMismatch(MismatchLocation {
+                                patch_span: *span,
+                                text_line: ptr,
+                            }));
                         }
-                        file1_ptr += 1;
+                        ptr += 1;
                     }
-                    PatchLine::Insert(ref data) => {
-                        file2_text.push(data.to_owned());
+                    (Direction::Forward, PatchLine::Insert(ref data, _))
+                    | (Direction::Reverse, PatchLine::Delete(ref data, _)) => {
+                        out_text.push(data.to_owned());
                     }
                 }
             }
         }
 
-        for i in file1_ptr..self.text.len() {
-            file2_text.push(
-                self.text
-                    .get(i)
-                    .ok_or_else(|| Error::AbruptInput(i))?
+        for i in ptr..text.len() {
+            out_text.push(
+                text.get(i)
+                    .ok_or(Error::AbruptInput(MismatchLocation {
+                        patch_span: (0, 0),
+                        text_line: i,
+                    }))?
                     .to_owned(),
             );
         }
 
-        Ok(file2_text)
+        Ok(out_text)
     }
 
-    pub fn convert(patch: &str) -> PatchResult<Patch> {
+    fn is_binary(patch: &Patch) -> bool {
+        patch
+            .metadata
+            .as_ref()
+            .is_some_and(|metadata| metadata.binary)
+    }
+
+    fn abrupt_at(context: &Context, text_line: usize) -> MismatchLocation {
+        MismatchLocation {
+            patch_span: context.header_span,
+            text_line,
+        }
+    }
+
+    fn apply_fuzzy(text: &[String], patch: &Patch, options: ApplyOptions) -> PatchResult<ApplyReport> {
+        if Self::is_binary(patch) {
+            return Ok(ApplyReport {
+                text: text.to_vec(),
+                hunks: Vec::new(),
+            });
+        }
+
+        let mut out_text = Vec::new();
+        let mut ptr: usize = 0;
+        let mut cumulative_offset: isize = 0;
+        let mut hunks = Vec::with_capacity(patch.contexts.len());
+
+        for context in &patch.contexts {
+            let base = context.header.file1_l as isize + cumulative_offset;
+            let (start, offset) = Self::locate_hunk(text, context, base, options)?;
+
+            for i in ptr..start {
+                out_text.push(
+                    text.get(i)
+                        .ok_or_else(|| Error::AbruptInput(Self::abrupt_at(context, i)))?
+                        .to_owned(),
+                );
+            }
+
+            Self::try_hunk(text, context, start, options, Some(&mut out_text))?;
+            ptr = start + Self::old_line_count(context);
+
+            // `offset` is relative to `base`, which already bakes in the
+            // drift from earlier hunks, so accumulate rather than replace.
+            cumulative_offset += offset;
+            hunks.push(HunkReport {
+                offset: cumulative_offset,
+            });
+        }
+
+        for i in ptr..text.len() {
+            out_text.push(
+                text.get(i)
+                    .ok_or(Error::AbruptInput(MismatchLocation {
+                        patch_span: (0, 0),
+                        text_line: i,
+                    }))?
+                    .to_owned(),
+            );
+        }
+
+        Ok(ApplyReport {
+            text: out_text,
+            hunks,
+        })
+    }
+
+    /// Searches for a position near `base` where `context` matches, trying
+    /// offset 0 first, then ±1, ±2, … up to `options.max_offset`.
+    fn locate_hunk(
+        text: &[String],
+        context: &Context,
+        base: isize,
+        options: ApplyOptions,
+    ) -> PatchResult<(usize, isize)> {
+        let offsets = std::iter::once(0).chain(
+            (1..=options.max_offset as isize).flat_map(|d| [d, -d]),
+        );
+
+        for d in offsets {
+            let candidate = base + d;
+            if candidate < 0 {
+                continue;
+            }
+            let candidate = candidate as usize;
+            if Self::try_hunk(text, context, candidate, options, None).is_ok() {
+                return Ok((candidate, d));
+            }
+        }
+
+        Err(Error::PatchInputMismatch(MismatchLocation {
+            patch_span: context.header_span,
+            text_line: base.max(0) as usize,
+        }))
+    }
+
+    /// Either verifies (`out = None`) or verifies-and-emits (`out = Some`)
+    /// `context` starting at `pos`, tolerating up to `fuzz` mismatches among
+    /// the hunk's leading and trailing context lines.
+    fn try_hunk(
+        text: &[String],
+        context: &Context,
+        pos: usize,
+        options: ApplyOptions,
+        mut out: Option<&mut Vec<String>>,
+    ) -> PatchResult<()> {
+        let old_count = Self::old_line_count(context);
+        let lead = context
+            .data
+            .iter()
+            .take_while(|line| matches!(line, PatchLine::Context(_, _)))
+            .count();
+        let trail = context
+            .data
+            .iter()
+            .rev()
+            .take_while(|line| matches!(line, PatchLine::Context(_, _)))
+            .count();
+        // `fuzz` is documented as 0-3, but nothing stops a caller from
+        // passing more; clamp so an oversized value can't make a hunk
+        // match on arbitrary unrelated text.
+        let fuzz = options.fuzz.min(3);
+        let skip_lead = fuzz.min(lead);
+        let skip_trail = fuzz.min(trail);
+
+        let mut ptr = pos;
+        let mut old_ix = 0;
+
+        for line in &context.data {
+            match line {
+                PatchLine::Context(data, span) => {
+                    let fuzzy = old_ix < skip_lead || old_ix >= old_count - skip_trail;
+                    let actual = text
+                        .get(ptr)
+                        .ok_or_else(|| Error::AbruptInput(Self::abrupt_at(context, ptr)))?;
+                    if !fuzzy && !Self::lines_match(actual, data, options.ignore_whitespace) {
+                        return Err(Error::PatchInputMismatch(MismatchLocation {
+                            patch_span: *span,
+                            text_line: ptr,
+                        }));
+                    }
+                    if let Some(out) = out.as_mut() {
+                        out.push(actual.to_owned());
+                    }
+                    ptr += 1;
+                    old_ix += 1;
+                }
+                PatchLine::Delete(data, span) => {
+                    let actual = text
+                        .get(ptr)
+                        .ok_or_else(|| Error::AbruptInput(Self::abrupt_at(context, ptr)))?;
+                    if !Self::lines_match(actual, data, options.ignore_whitespace) {
+                        return Err(Error::PatchInputMismatch(MismatchLocation {
+                            patch_span: *span,
+                            text_line: ptr,
+                        }));
+                    }
+                    ptr += 1;
+                    old_ix += 1;
+                }
+                PatchLine::Insert(data, _) => {
+                    if let Some(out) = out.as_mut() {
+                        out.push(data.to_owned());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn old_line_count(context: &Context) -> usize {
+        context
+            .data
+            .iter()
+            .filter(|line| !matches!(line, PatchLine::Insert(_, _)))
+            .count()
+    }
+
+    fn lines_match(actual: &str, expected: &str, ignore_whitespace: bool) -> bool {
+        if ignore_whitespace {
+            Self::collapse_whitespace(actual) == Self::collapse_whitespace(expected)
+        } else {
+            actual == expected
+        }
+    }
+
+    /// Trims trailing whitespace and collapses interior runs of whitespace
+    /// to a single space, so reindented or CRLF/LF-churned lines compare
+    /// equal.
+    fn collapse_whitespace(line: &str) -> String {
+        let mut collapsed = String::with_capacity(line.len());
+        let mut in_run = false;
+        for ch in line.trim_end().chars() {
+            if ch.is_whitespace() {
+                if !in_run {
+                    collapsed.push(' ');
+                }
+                in_run = true;
+            } else {
+                collapsed.push(ch);
+                in_run = false;
+            }
+        }
+        collapsed
+    }
+
+    /// Parses every `--- `/`+++ ` file block in `patch` into its own [`Patch`].
+    fn convert_patches(patch: &str) -> PatchResult<Vec<Patch>> {
         let peg_patch = Self::parse(Rule::patch, patch)?
             .next()
             .ok_or(Error::NotFound("patch"))?;
 
+        let mut patches = Vec::new();
+
+        for file_patch in peg_patch.into_inner() {
+            match file_patch.as_rule() {
+                Rule::file_patch => patches.push(Self::convert_file_patch(file_patch)?),
+                Rule::EOI => {}
+                _ => {}
+            }
+        }
+
+        Ok(patches)
+    }
+
+    fn convert_file_patch(file_patch: Pair<'_, Rule>) -> PatchResult<Patch> {
         let mut contexts = Vec::new();
         let mut input = None;
         let mut output = None;
+        let mut metadata = None;
+        let mut binary = false;
 
-        for patch_element in peg_patch.into_inner() {
-            match patch_element.as_rule() {
-                Rule::file1_header => {
-                    for header_element in patch_element.into_inner() {
-                        if let Rule::path = header_element.as_rule() {
-                            input = Some(header_element.as_span().as_str().to_owned());
-                        }
-                    }
-                }
-                Rule::file2_header => {
-                    for header_element in patch_element.into_inner() {
-                        if let Rule::path = header_element.as_rule() {
-                            output = Some(header_element.as_span().as_str().to_owned());
-                        }
-                    }
-                }
-                Rule::context => {
-                    let mut peg_context = patch_element.into_inner();
-                    let context_header = peg_context
-                        .next()
-                        .ok_or(Error::NotFound("context_header"))?;
-                    let context_header = if let Rule::context_header = context_header.as_rule() {
-                        Self::get_context_header(context_header)?
-                    } else {
-                        return Err(Error::MalformedPatch(
-                            "Context header is not at the start of a context",
-                        ));
-                    };
-
-                    let mut context = Context {
-                        header: context_header,
-                        data: Vec::new(),
-                    };
-                    for line in peg_context {
-                        match line.as_rule() {
-                            Rule::line_context => context
-                                .data
-                                .push(PatchLine::Context(line.as_span().as_str().to_owned())),
-                            Rule::line_deleted => context
-                                .data
-                                .push(PatchLine::Delete(line.as_span().as_str().to_owned())),
-                            Rule::line_inserted => context
-                                .data
-                                .push(PatchLine::Insert(line.as_span().as_str().to_owned())),
+        for element in file_patch.into_inner() {
+            match element.as_rule() {
+                Rule::git_header => metadata = Some(Self::convert_git_header(element)?),
+                Rule::file_body => {
+                    for body_element in element.into_inner() {
+                        match body_element.as_rule() {
+                            Rule::file1_header => {
+                                for header_element in body_element.into_inner() {
+                                    if let Rule::path = header_element.as_rule() {
+                                        input = Some(header_element.as_span().as_str().to_owned());
+                                    }
+                                }
+                            }
+                            Rule::file2_header => {
+                                for header_element in body_element.into_inner() {
+                                    if let Rule::path = header_element.as_rule() {
+                                        output = Some(header_element.as_span().as_str().to_owned());
+                                    }
+                                }
+                            }
+                            Rule::context => contexts.push(Self::convert_context(body_element)?),
+                            Rule::binary_marker => {
+                                binary = true;
+                                let mut paths = body_element.into_inner();
+                                input = paths.next().map(|p| p.as_span().as_str().to_owned());
+                                output = paths.next().map(|p| p.as_span().as_str().to_owned());
+                            }
                             _ => {}
                         }
                     }
-                    contexts.push(context);
                 }
                 _ => {}
             }
         }
 
-        let input = input.ok_or_else(|| Error::NotFound("path (input)"))?;
-        let output = output.ok_or_else(|| Error::NotFound("path (output)"))?;
+        match metadata.as_mut() {
+            Some(metadata) => metadata.binary = binary,
+            // Plain `diff -r` emits a bare `Binary files ... differ` marker
+            // with no preceding `diff --git` header, so there is no
+            // FileMetadata for is_binary()/apply() to read the flag from
+            // unless we synthesize one here.
+            None if binary => {
+                metadata = Some(FileMetadata {
+                    binary: true,
+                    ..FileMetadata::default()
+                });
+            }
+            None => {}
+        }
+
+        // A binary diff or a pure rename with no content change carries no
+        // `---`/`+++` headers at all; fall back to the paths recorded in
+        // the git extended header.
+        let input = input
+            .or_else(|| metadata.as_ref().and_then(|m| m.old_path.clone()))
+            .ok_or(Error::NotFound("path (input)"))?;
+        let output = output
+            .or_else(|| metadata.as_ref().and_then(|m| m.new_path.clone()))
+            .ok_or(Error::NotFound("path (output)"))?;
 
-        let patch = Patch {
+        Ok(Patch {
             input,
             output,
             contexts,
+            metadata,
+        })
+    }
+
+    fn convert_context(context_pair: Pair<'_, Rule>) -> PatchResult<Context> {
+        let mut peg_context = context_pair.into_inner();
+        let context_header_pair = peg_context
+            .next()
+            .ok_or(Error::NotFound("context_header"))?;
+        let header_span = {
+            let span = context_header_pair.as_span();
+            (span.start(), span.end())
+        };
+        let context_header = if let Rule::context_header = context_header_pair.as_rule() {
+            Self::get_context_header(context_header_pair)?
+        } else {
+            return Err(Error::MalformedPatch(
+                "Context header is not at the start of a context",
+            ));
+        };
+
+        let mut context = Context {
+            header: context_header,
+            header_span,
+            data: Vec::new(),
         };
+        for line in peg_context {
+            let span = {
+                let s = line.as_span();
+                (s.start(), s.end())
+            };
+            match line.as_rule() {
+                Rule::line_context => context
+                    .data
+                    .push(PatchLine::Context(line.as_span().as_str().to_owned(), span)),
+                Rule::line_deleted => context
+                    .data
+                    .push(PatchLine::Delete(line.as_span().as_str().to_owned(), span)),
+                Rule::line_inserted => context
+                    .data
+                    .push(PatchLine::Insert(line.as_span().as_str().to_owned(), span)),
+                _ => {}
+            }
+        }
+
+        Ok(context)
+    }
+
+    fn convert_git_header(git_header: Pair<'_, Rule>) -> PatchResult<FileMetadata> {
+        let mut metadata = FileMetadata::default();
+
+        for element in git_header.into_inner() {
+            match element.as_rule() {
+                Rule::diff_git_line => {
+                    let mut paths = element.into_inner();
+                    metadata.old_path = paths.next().map(|p| p.as_span().as_str().to_owned());
+                    metadata.new_path = paths.next().map(|p| p.as_span().as_str().to_owned());
+                }
+                Rule::old_mode_line => {
+                    metadata.old_mode = Self::first_child_str(element);
+                }
+                Rule::new_mode_line => {
+                    metadata.new_mode = Self::first_child_str(element);
+                }
+                Rule::deleted_file_line => {
+                    metadata.is_deleted_file = true;
+                    metadata.old_mode = Self::first_child_str(element);
+                }
+                Rule::new_file_line => {
+                    metadata.is_new_file = true;
+                    metadata.new_mode = Self::first_child_str(element);
+                }
+                Rule::copy_from_line => {
+                    metadata.is_copy = true;
+                    metadata.old_path = Self::first_child_str(element);
+                }
+                Rule::copy_to_line => {
+                    metadata.is_copy = true;
+                    metadata.new_path = Self::first_child_str(element);
+                }
+                Rule::rename_from_line => {
+                    metadata.is_rename = true;
+                    metadata.old_path = Self::first_child_str(element);
+                }
+                Rule::rename_to_line => {
+                    metadata.is_rename = true;
+                    metadata.new_path = Self::first_child_str(element);
+                }
+                Rule::similarity_index_line => {
+                    metadata.similarity_index = Self::first_child_str(element)
+                        .map(|digits| digits.parse())
+                        .transpose()?;
+                }
+                Rule::index_line => {
+                    // The trailing mode is only present when it didn't
+                    // change, so it applies to both sides.
+                    if let Some(mode) = element.into_inner().find(|p| p.as_rule() == Rule::mode) {
+                        let mode = mode.as_span().as_str().to_owned();
+                        metadata.old_mode.get_or_insert_with(|| mode.clone());
+                        metadata.new_mode.get_or_insert(mode);
+                    }
+                }
+                _ => {}
+            }
+        }
 
-        Ok(patch)
+        Ok(metadata)
+    }
+
+    fn first_child_str(pair: Pair<'_, Rule>) -> Option<String> {
+        pair.into_inner()
+            .next()
+            .map(|child| child.as_span().as_str().to_owned())
     }
 
     fn get_context_header(header: Pair<'_, Rule>) -> PatchResult<ContextHeader> {
@@ -192,8 +640,364 @@ impl PatchProcessor {
                 _ => {}
             }
         }
-        output.file1_l -= 1;
-        output.file2_l -= 1;
+        // `@@ -0,0 +1,N @@` (new file) and `@@ -1,N +0,0 @@` (deleted file)
+        // record line 0 for the empty side, which is already the right
+        // 0-indexed position for "no content here" — decrementing it would
+        // underflow, and would also shift it away from 0.
+        if output.file1_s > 0 {
+            output.file1_l -= 1;
+        }
+        if output.file2_s > 0 {
+            output.file2_l -= 1;
+        }
         Ok(output)
     }
 }
+
+impl PatchSet {
+    /// Parses a patch containing one or more concatenated file blocks, such
+    /// as the output of `git diff` or `diff -ru`.
+    pub fn convert(patch: &str) -> PatchResult<Self> {
+        Ok(Self {
+            patches: PatchProcessor::convert_patches(patch)?,
+        })
+    }
+
+    /// Applies every file patch in this set to the matching entry of
+    /// `files` (keyed by each [`Patch`]'s `input` path, or by the git
+    /// header's `rename from` path for a renamed file), returning the
+    /// patched contents keyed by the corresponding `output` path.
+    pub fn process(
+        &self,
+        files: HashMap<String, Vec<String>>,
+    ) -> PatchResult<HashMap<String, Vec<String>>> {
+        let mut result = HashMap::with_capacity(self.patches.len());
+
+        for patch in &self.patches {
+            let rename = patch.metadata.as_ref().filter(|m| m.is_rename);
+
+            let input_key = rename
+                .and_then(|m| m.old_path.as_ref())
+                .unwrap_or(&patch.input);
+            let text = files
+                .get(input_key)
+                .ok_or(Error::NotFound("input file"))?;
+
+            let output_key = rename
+                .and_then(|m| m.new_path.clone())
+                .unwrap_or_else(|| patch.output.clone());
+
+            let output = PatchProcessor::apply(text, patch, Direction::Forward)?;
+            result.insert(output_key, output);
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_applies_patchset_and_honors_rename_for_key_lookup() {
+        let patch_src = "\
+--- a/foo.txt
++++ b/foo.txt
+@@ -1,1 +1,1 @@
+-old foo
++new foo
+diff --git a/old_name.txt b/new_name.txt
+rename from old_name.txt
+rename to new_name.txt
+--- a/old_name.txt
++++ b/new_name.txt
+@@ -1,1 +1,1 @@
+-old bar
++new bar
+";
+        let set = PatchSet::convert(patch_src).expect("patch set should parse");
+
+        let mut files = HashMap::new();
+        files.insert("a/foo.txt".to_string(), vec!["old foo".to_string()]);
+        files.insert("old_name.txt".to_string(), vec!["old bar".to_string()]);
+
+        let result = set.process(files).expect("patch set should apply");
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result["b/foo.txt"], vec!["new foo".to_string()]);
+        assert_eq!(result["new_name.txt"], vec!["new bar".to_string()]);
+    }
+
+    #[test]
+    fn patchset_parses_multiple_file_blocks() {
+        let patch_src = "\
+--- a/foo.txt
++++ b/foo.txt
+@@ -1,2 +1,2 @@
+-old foo
++new foo
+ same
+--- a/bar.txt
++++ b/bar.txt
+@@ -1,1 +1,1 @@
+-old bar
++new bar
+";
+        let set = PatchSet::convert(patch_src).expect("patch set should parse");
+
+        assert_eq!(set.patches.len(), 2);
+        assert_eq!(set.patches[0].input, "a/foo.txt");
+        assert_eq!(set.patches[0].output, "b/foo.txt");
+        assert_eq!(set.patches[1].input, "a/bar.txt");
+        assert_eq!(set.patches[1].output, "b/bar.txt");
+    }
+
+    #[test]
+    fn process_reverse_turns_output_back_into_input() {
+        let patch_src = "\
+--- a/foo.txt
++++ b/foo.txt
+@@ -1,2 +1,2 @@
+-old foo
++new foo
+ same
+";
+        let output_text = vec!["new foo".to_owned(), "same".to_owned()];
+        let processor = PatchProcessor::converted(output_text, patch_src).unwrap();
+
+        let input_text = processor.process_reverse().unwrap();
+
+        assert_eq!(input_text, vec!["old foo".to_owned(), "same".to_owned()]);
+    }
+
+    /// Builds a single-line context hunk recorded at old-file line `recorded`
+    /// (0-indexed, matching [`ContextHeader`]'s post-decrement convention).
+    fn single_line_context_hunk(recorded: usize, line: &str) -> Context {
+        Context {
+            header: ContextHeader {
+                file1_l: recorded,
+                file1_s: 1,
+                file2_l: recorded,
+                file2_s: 1,
+            },
+            header_span: (0, 0),
+            data: vec![PatchLine::Context(line.to_owned(), (0, 0))],
+        }
+    }
+
+    #[test]
+    fn process_with_options_tracks_cumulative_offset_across_hunks() {
+        // The true position of each hunk's content drifts further from its
+        // recorded line number than `max_offset` alone can search for in a
+        // single hop: +2 at hunk 1, +4 at hunk 2, +6 at hunk 3. Each hop is
+        // only 2 lines, within `max_offset`, but only if the search base for
+        // each hunk carries forward the *total* drift established by every
+        // hunk before it.
+        let text: Vec<String> = [
+            "a0", "a1", "a2", "T1", "b1", "b2", "b3", "b4", "T2", "c1", "c2", "c3", "c4", "T3",
+        ]
+        .into_iter()
+        .map(str::to_owned)
+        .collect();
+
+        let patch = Patch {
+            input: "in".to_owned(),
+            output: "out".to_owned(),
+            contexts: vec![
+                single_line_context_hunk(1, "T1"),
+                single_line_context_hunk(4, "T2"),
+                single_line_context_hunk(7, "T3"),
+            ],
+            metadata: None,
+        };
+
+        let processor = PatchProcessor {
+            text: text.clone(),
+            patch,
+        };
+        let options = ApplyOptions {
+            fuzz: 0,
+            max_offset: 2,
+            ignore_whitespace: false,
+        };
+
+        let report = processor
+            .process_with_options(options)
+            .expect("cumulative drift should stay within max_offset at every hunk");
+
+        let offsets: Vec<isize> = report.hunks.iter().map(|h| h.offset).collect();
+        assert_eq!(offsets, vec![2, 4, 6]);
+        assert_eq!(report.text, text);
+    }
+
+    #[test]
+    fn ignore_whitespace_matches_reindented_lines() {
+        let patch = Patch {
+            input: "in".to_owned(),
+            output: "out".to_owned(),
+            contexts: vec![Context {
+                header: ContextHeader {
+                    file1_l: 0,
+                    file1_s: 1,
+                    file2_l: 0,
+                    file2_s: 1,
+                },
+                header_span: (0, 0),
+                data: vec![
+                    PatchLine::Delete("\tfn old() {}".to_owned(), (0, 0)),
+                    PatchLine::Insert("    fn new() {}".to_owned(), (0, 0)),
+                ],
+            }],
+            metadata: None,
+        };
+        // Reindented with spaces instead of a tab, and trailing whitespace,
+        // relative to the delete line recorded in the patch.
+        let text = vec!["  fn old() {}  ".to_owned()];
+
+        let processor = PatchProcessor { text, patch };
+
+        let strict = processor.process_with_options(ApplyOptions::default());
+        assert!(strict.is_err());
+
+        let lenient = processor
+            .process_with_options(ApplyOptions {
+                ignore_whitespace: true,
+                ..ApplyOptions::default()
+            })
+            .expect("whitespace-insensitive match should succeed");
+        assert_eq!(lenient.text, vec!["    fn new() {}".to_owned()]);
+    }
+
+    #[test]
+    fn convert_parses_git_rename_with_mode_change() {
+        let patch_src = "\
+diff --git a/old_name.txt b/new_name.txt
+old mode 100644
+new mode 100755
+similarity index 100%
+rename from old_name.txt
+rename to new_name.txt
+";
+        let patch = PatchProcessor::convert(patch_src).expect("git rename header should parse");
+
+        let metadata = patch.metadata.as_ref().expect("rename carries metadata");
+        assert!(metadata.is_rename);
+        assert_eq!(metadata.old_path.as_deref(), Some("old_name.txt"));
+        assert_eq!(metadata.new_path.as_deref(), Some("new_name.txt"));
+        assert_eq!(metadata.old_mode.as_deref(), Some("100644"));
+        assert_eq!(metadata.new_mode.as_deref(), Some("100755"));
+        assert_eq!(metadata.similarity_index, Some(100));
+        assert!(!metadata.binary);
+
+        // A pure rename with no body falls back to the git header's paths.
+        assert_eq!(patch.input, "old_name.txt");
+        assert_eq!(patch.output, "new_name.txt");
+    }
+
+    #[test]
+    fn convert_parses_binary_marker() {
+        let patch_src = "\
+diff --git a/image.png b/image.png
+index abc123..def456 100644
+Binary files a/image.png and b/image.png differ
+";
+        let patch = PatchProcessor::convert(patch_src).expect("binary marker should parse");
+
+        let metadata = patch.metadata.as_ref().expect("binary diff carries metadata");
+        assert!(metadata.binary);
+        assert_eq!(metadata.old_mode.as_deref(), Some("100644"));
+        assert_eq!(metadata.new_mode.as_deref(), Some("100644"));
+        assert_eq!(patch.input, "a/image.png");
+        assert_eq!(patch.output, "b/image.png");
+    }
+
+    #[test]
+    fn convert_parses_bare_binary_marker() {
+        // `diff -r` emits this marker with no preceding `diff --git` header,
+        // so metadata must be synthesized rather than merely updated.
+        let patch_src = "Binary files a/image.png and b/image.png differ\n";
+        let patch = PatchProcessor::convert(patch_src).expect("binary marker should parse");
+
+        let metadata = patch.metadata.as_ref().expect("binary diff carries metadata");
+        assert!(metadata.binary);
+        assert_eq!(metadata.old_mode, None);
+        assert_eq!(metadata.new_mode, None);
+        assert_eq!(patch.input, "a/image.png");
+        assert_eq!(patch.output, "b/image.png");
+    }
+
+    #[test]
+    fn apply_mismatch_reports_patch_line_span() {
+        let patch_src = "\
+--- a/f.txt
++++ b/f.txt
+@@ -1,2 +1,2 @@
+-old
++new
+ same
+";
+        // Byte span of the context line's recorded content, to check that
+        // the mismatch error points at the right place in `patch_src`.
+        let context_start = patch_src.find("same").unwrap();
+        let context_span = (context_start, context_start + "same".len());
+
+        let wrong_text = vec!["old".to_owned(), "different".to_owned()];
+        let processor = PatchProcessor::converted(wrong_text, patch_src).unwrap();
+
+        let err = processor
+            .process()
+            .expect_err("mismatched context line should fail to apply");
+
+        match err {
+            Error::PatchInputMismatch(loc) => {
+                assert_eq!(loc.patch_span, context_span);
+                assert_eq!(loc.text_line, 1);
+            }
+            other => panic!("expected PatchInputMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn convert_and_apply_new_file_hunk() {
+        let patch_src = "\
+diff --git a/new.txt b/new.txt
+new file mode 100644
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1,2 @@
++one
++two
+";
+        let patch = PatchProcessor::convert(patch_src).expect("new-file hunk should not underflow");
+        assert_eq!(patch.contexts[0].header.file1_l, 0);
+
+        let output = PatchProcessor::converted(Vec::new(), patch_src)
+            .unwrap()
+            .process()
+            .expect("new-file hunk should apply against empty input");
+        assert_eq!(output, vec!["one".to_owned(), "two".to_owned()]);
+    }
+
+    #[test]
+    fn convert_and_apply_deleted_file_hunk() {
+        let patch_src = "\
+diff --git a/old.txt b/old.txt
+deleted file mode 100644
+--- a/old.txt
++++ /dev/null
+@@ -1,2 +0,0 @@
+-one
+-two
+";
+        let patch =
+            PatchProcessor::convert(patch_src).expect("deleted-file hunk should not underflow");
+        assert_eq!(patch.contexts[0].header.file2_l, 0);
+
+        let output = PatchProcessor::converted(vec!["one".to_owned(), "two".to_owned()], patch_src)
+            .unwrap()
+            .process()
+            .expect("deleted-file hunk should apply against matching input");
+        assert!(output.is_empty());
+    }
+}